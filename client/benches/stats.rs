@@ -0,0 +1,194 @@
+// 统计开销基准
+//
+// 1. 记录统计：多个任务（与虚拟用户一样各自通过 TaskStats::shard 分到分片）不停写入请求记录，
+//    同时一个任务每 100ms 调用一次 TaskStats::merged（与上报任务相同），分别给出每条记录的平均写入耗时
+//    （构造记录、加分片锁和 StatsShard::push）和每次合并的耗时。不经过网络，任务数不同时可以看出分片锁的争用
+// 2. 端到端：用 run_task 执行一个真实任务，向本机的 HTTP 服务发送请求，经过渲染模板、检查响应、
+//    提取变量和记录统计的完整流程，给出吞吐量，并列出改为分片之前在同样负载下测得的数值作为参照
+//
+// cargo bench --bench stats -- [记录总数] [端到端秒数]
+
+use api_burner::{BodyRecord, ConnectionStats, RequestRecord, ResponseRecord, TaskConfig, TaskStats};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio::task::JoinSet;
+
+const RESPONSE_BODY: &str = r#"{"data": {"token": "bench-token", "items": [{"id": 1}, {"id": 2}, {"id": 3}]}}"#;
+
+// 改为分片之前（所有请求加同一把 tokio::sync::Mutex<Stats> 原地更新统计）的端到端吞吐量（请求/秒），
+// 在 1 核的测试机上以同样的场景、16 个工作单元共 50000 个请求测得，只有在同一台机器上运行时才能直接比较
+const GLOBAL_LOCK_BASELINE_RPS: f64 = 45023.0;
+
+#[tokio::main]
+async fn main() {
+    // cargo bench 会额外传入 --bench 参数
+    let mut args = std::env::args().skip(1).filter(|arg| !arg.starts_with("--"));
+    let records = args.next().map_or(2_000_000, |records| records.parse().expect("记录总数必须是整数"));
+    let seconds = args.next().map_or(10, |seconds| seconds.parse().expect("端到端秒数必须是整数"));
+
+    println!("记录统计：共 {} 条记录", records);
+    let cpus = num_cpus::get();
+    let mut counts = vec![1, cpus, cpus * 16];
+    counts.dedup();
+    for tasks in counts {
+        record_stats(records, tasks).await;
+    }
+
+    end_to_end(seconds, cpus * 16).await;
+}
+
+// tasks 个任务共写入 records 条记录，同时每 100ms 合并一次
+async fn record_stats(records: u64, tasks: usize) {
+    let stats = Arc::new(TaskStats::new(Arc::new(std::sync::Mutex::new(ConnectionStats::new()))));
+    let running = Arc::new(AtomicBool::new(true));
+    let reader = {
+        let (stats, running) = (stats.clone(), running.clone());
+        tokio::spawn(async move {
+            let (mut merges, mut total, mut max) = (0u32, Duration::ZERO, Duration::ZERO);
+            while running.load(Ordering::Relaxed) {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                let start = Instant::now();
+                drop(stats.merged().await);
+                let elapsed = start.elapsed();
+                merges += 1;
+                total += elapsed;
+                max = max.max(elapsed);
+            }
+            (merges, total, max)
+        })
+    };
+
+    let per_task = records / tasks as u64;
+    let endpoint: Arc<str> = "bench".into();
+    let start = Instant::now();
+    let mut writers = JoinSet::new();
+    for _ in 0..tasks {
+        let (stats, endpoint) = (stats.clone(), endpoint.clone());
+        writers.spawn(async move {
+            let shard = stats.shard();
+            for index in 0..per_task {
+                let latency = Duration::from_micros(500 + index % 1000);
+                shard.lock().unwrap().push(record(&endpoint, latency), None);
+                // 真实的工作单元在两次记录之间会等待网络，这里定期让出，合并任务才有机会运行
+                if index % 256 == 0 {
+                    tokio::task::yield_now().await;
+                }
+            }
+        });
+    }
+    while writers.join_next().await.is_some() {}
+    let elapsed = start.elapsed();
+    running.store(false, Ordering::Relaxed);
+    let (merges, merge_total, merge_max) = reader.await.unwrap();
+
+    let written = per_task * tasks as u64;
+    assert_eq!(stats.merged().await.total_requests, written);
+    println!(
+        "{} 个任务：每条记录 {:.0}ns（{} 条），合并 {} 次，平均 {:.1}µs，最长 {:.1}µs",
+        tasks,
+        elapsed.as_nanos() as f64 / written as f64,
+        written,
+        merges,
+        merge_total.as_secs_f64() * 1_000_000.0 / merges.max(1) as f64,
+        merge_max.as_secs_f64() * 1_000_000.0
+    );
+}
+
+fn record(endpoint: &Arc<str>, latency: Duration) -> RequestRecord {
+    let at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+    RequestRecord {
+        at,
+        endpoint: endpoint.clone(),
+        retries: 0,
+        connections: Vec::new(),
+        reused_connections: 1,
+        response: Some(ResponseRecord {
+            status: http::StatusCode::OK,
+            version: http::Version::HTTP_11,
+            latency,
+            body: Some(BodyRecord { ttfb: latency, download: Duration::from_micros(20), bytes: RESPONSE_BODY.len() as u64 }),
+        }),
+        failed_checks: Vec::new(),
+        result: Ok(latency),
+    }
+}
+
+// 以 users 个虚拟用户执行 seconds 秒的两步场景
+async fn end_to_end(seconds: u64, users: usize) {
+    let addr = serve().await;
+    let task: TaskConfig = serde_json::from_value(serde_json::json!({
+        "duration": seconds,
+        "concurrent_requests": users,
+        "checks": [{ "status": ["2xx"] }, { "body_contains": "items" }],
+        "scenario": [
+            {
+                "name": "session",
+                "url": format!("http://{}/session", addr),
+                "method": "GET",
+                "extract": [
+                    { "from": "json", "var": "token", "path": "data.token" },
+                    { "from": "json", "var": "item", "path": "$.data.items.1.id" }
+                ]
+            },
+            {
+                "name": "item",
+                "url": format!("http://{}/items/{{{{item}}}}", addr),
+                "method": "GET",
+                "headers": { "Authorization": "Bearer {{token}}" },
+                "query_params": { "token": "{{token}}" },
+                "checks": [{ "json_path": { "path": "data.token", "equals": "bench-token" } }]
+            }
+        ]
+    }))
+    .unwrap();
+
+    let summary = api_burner::run_task(task).await.unwrap();
+    let summary = serde_json::to_value(&summary).unwrap();
+    let total = summary["stats"]["total_requests"].as_u64().unwrap_or_default();
+    let successful = summary["stats"]["success_count"].as_u64().unwrap_or_default();
+    let duration = summary["duration"].as_f64().unwrap_or_default() / 1000.0;
+    println!(
+        "\n端到端：{} 个虚拟用户，吞吐量 {:.0} 请求/秒（{} 个请求，{} 个成功），改为分片之前: {:.0} 请求/秒",
+        users,
+        total as f64 / duration,
+        total,
+        successful,
+        GLOBAL_LOCK_BASELINE_RPS
+    );
+}
+
+// 本机的 HTTP 服务：读完请求头后回复固定的 JSON 响应，连接保持复用
+async fn serve() -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+        RESPONSE_BODY.len(),
+        RESPONSE_BODY
+    );
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let response = response.clone();
+            tokio::spawn(async move {
+                let mut stream = tokio::io::BufStream::new(stream);
+                let mut line = String::new();
+                loop {
+                    line.clear();
+                    match stream.read_line(&mut line).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(_) if line == "\r\n" => {
+                            if stream.write_all(response.as_bytes()).await.is_err() || stream.flush().await.is_err() {
+                                break;
+                            }
+                        }
+                        Ok(_) => {}
+                    }
+                }
+            });
+        }
+    });
+    addr
+}
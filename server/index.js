@@ -100,10 +100,55 @@ wss.on('connection', (ws) => {
                     const client = clients.get(clientId);
                     if (client) {
                         client.stats = data.stats;
+                        client.stats_task_id = data.task_id;
                         client.last_active = new Date().toISOString();
                         console.log(`客户端 ${clientId} 统计信息更新:`, data.stats);
                     }
                 }
+            } else if (data.type === 'task_complete' || data.type === 'task_failed') {
+                // 任务结束：task_complete 带有完整的最终结果，task_failed 带有无法执行的原因
+                if (clientId) {
+                    const client = clients.get(clientId);
+                    if (client) {
+                        if (client.current_task === data.task_id) {
+                            client.current_task = null;
+                        }
+                        client.last_task_result = data;
+                        client.last_active = new Date().toISOString();
+                    }
+                }
+                if (data.type === 'task_complete') {
+                    console.log(`客户端 ${clientId} 任务 ${data.task_id} 已结束，原因: ${data.end_reason}`);
+                } else {
+                    console.log(`客户端 ${clientId} 任务 ${data.task_id} 执行失败: ${data.error}`);
+                }
+            } else if (data.type === 'update_rejected') {
+                console.warn(`客户端 ${clientId} 拒绝调整任务 ${data.task_id ?? '（全部）'}: ${data.error}`);
+            } else if (data.type === 'task_history') {
+                if (clientId) {
+                    const client = clients.get(clientId);
+                    if (client) {
+                        client.task_history = data.tasks;
+                        client.last_active = new Date().toISOString();
+                    }
+                }
+            } else if (data.type === 'time_sync') {
+                // 时钟同步：原样带回客户端发送时间，并附上服务端当前时间
+                ws.send(JSON.stringify({
+                    type: 'time_sync_reply',
+                    client_time: data.client_time,
+                    server_time: Date.now()
+                }));
+            } else if (data.type === 'task_accepted') {
+                if (clientId) {
+                    const client = clients.get(clientId);
+                    if (client) {
+                        client.current_task = data.task_id;
+                        client.clock_offset = data.clock_offset;
+                        client.last_active = new Date().toISOString();
+                    }
+                }
+                console.log(`客户端 ${clientId} 已接受任务 ${data.task_id}`);
             } else if (data.type === 'ping') {
                 if (data.client_id && data.client_id === clientId) {
                     console.log(`收到来自客户端 ${data.client_id} 的ping消息`);
@@ -171,7 +216,13 @@ app.get('/ping', (req, res) => {
 
 // 下发任务到所有客户端
 app.post('/assign_all', (req, res) => {
-    const task = req.body;
+    // 同一次下发的所有客户端使用相同的任务ID
+    const { start_delay, ...body } = req.body;
+    const task = { task_id: uuidv4(), ...body };
+    // 指定 start_delay（毫秒）时，所有客户端在同一时刻开始
+    if (typeof start_delay === 'number' && task.start_at === undefined) {
+        task.start_at = Date.now() + start_delay;
+    }
     const results = {
         success: 0,
         failed: 0,
@@ -193,6 +244,87 @@ app.post('/assign_all', (req, res) => {
 
     res.json({
         message: `任务已下发到 ${clients.size} 个客户端，成功: ${results.success}，失败: ${results.failed}`,
+        task_id: task.task_id,
+        start_at: task.start_at,
+        errors: results.errors
+    });
+});
+
+// 向所有客户端广播控制消息
+function broadcast(message) {
+    const results = {
+        success: 0,
+        failed: 0,
+        errors: []
+    };
+
+    clients.forEach((client, clientId) => {
+        try {
+            client.ws.send(JSON.stringify(message));
+            results.success++;
+        } catch (err) {
+            results.failed++;
+            results.errors.push(`客户端 ${clientId} 发送失败: ${err.message}`);
+        }
+    });
+
+    return results;
+}
+
+// 停止所有客户端的任务：停止发送新请求，等待在途请求完成后上报最终统计
+app.post('/stop_all', (req, res) => {
+    const results = broadcast({ type: 'stop', task_id: req.body?.task_id });
+    res.json({
+        message: `停止指令已下发到 ${clients.size} 个客户端，成功: ${results.success}，失败: ${results.failed}`,
+        errors: results.errors
+    });
+});
+
+// 中止所有客户端的任务：立即丢弃所有请求
+app.post('/abort_all', (req, res) => {
+    const results = broadcast({ type: 'abort', task_id: req.body?.task_id });
+    res.json({
+        message: `中止指令已下发到 ${clients.size} 个客户端，成功: ${results.success}，失败: ${results.failed}`,
+        errors: results.errors
+    });
+});
+
+// 暂停所有客户端的任务
+app.post('/pause_all', (req, res) => {
+    const results = broadcast({ type: 'pause', task_id: req.body?.task_id });
+    res.json({
+        message: `暂停指令已下发到 ${clients.size} 个客户端，成功: ${results.success}，失败: ${results.failed}`,
+        errors: results.errors
+    });
+});
+
+// 恢复所有客户端的任务
+app.post('/resume_all', (req, res) => {
+    const results = broadcast({ type: 'resume', task_id: req.body?.task_id });
+    res.json({
+        message: `恢复指令已下发到 ${clients.size} 个客户端，成功: ${results.success}，失败: ${results.failed}`,
+        errors: results.errors
+    });
+});
+
+// 调整所有客户端任务的目标值（开环模式为请求/秒，闭环模式为虚拟用户数）
+app.post('/update_all', (req, res) => {
+    const target = req.body?.target;
+    if (typeof target !== 'number' || target < 0) {
+        return res.status(400).json({ message: '缺少有效的 target 字段' });
+    }
+    const results = broadcast({ type: 'update', task_id: req.body.task_id, target });
+    res.json({
+        message: `调整指令已下发到 ${clients.size} 个客户端，成功: ${results.success}，失败: ${results.failed}`,
+        errors: results.errors
+    });
+});
+
+// 请求所有客户端上报已结束任务的结果，结果异步更新到客户端列表的 task_history 字段
+app.post('/history_all', (req, res) => {
+    const results = broadcast({ type: 'history' });
+    res.json({
+        message: `历史查询已下发到 ${clients.size} 个客户端，成功: ${results.success}，失败: ${results.failed}`,
         errors: results.errors
     });
 });